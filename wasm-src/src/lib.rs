@@ -46,6 +46,11 @@ pub fn process_safe(
         "bayer_dither" => bayer_dither_impl(pixels, width, height, params),
         "flood_fill" => flood_fill_impl(pixels, width, height, params),
         "box_blur" => box_blur_impl(pixels, width, height, params),
+        "quantize" => quantize_impl(pixels, width, height, params),
+        "gaussian_blur" => gaussian_blur_impl(pixels, width, height, params),
+        "unsharp_mask" => unsharp_mask_impl(pixels, width, height, params),
+        "perspective_warp" => perspective_warp_impl(pixels, width, height, params),
+        "perceptual_diff" => perceptual_diff_impl(pixels, width, height, params),
         _ => ProcessResult {
             success: false,
             error: format!("Unknown operation: {}", operation),
@@ -62,45 +67,58 @@ fn floyd_steinberg_impl(pixels: &[u8], width: u32, height: u32, params: &[f32])
             data: vec![],
         };
     }
-    
+
     let threshold = params[0] as u8;
+    let kernel_id = if params.len() > 1 { params[1] as usize } else { 0 };
+    let serpentine = params.len() > 2 && params[2] != 0.0;
+
+    let kernel = match kernel_id {
+        0 => &FLOYD_STEINBERG_KERNEL,
+        1 => &ATKINSON_KERNEL,
+        2 => &JARVIS_JUDICE_NINKE_KERNEL,
+        3 => &STUCKI_KERNEL,
+        4 => &SIERRA_KERNEL,
+        _ => return ProcessResult {
+            success: false,
+            error: format!("Unsupported dither kernel: {}", kernel_id),
+            data: vec![],
+        }
+    };
+
     let mut result = pixels.to_vec();
     let w = width as usize;
     let h = height as usize;
-    
-    // Floyd-Steinberg error diffusion
+
+    // Generalized error diffusion over a kernel offset/weight/divisor table.
     for y in 0..h {
-        for x in 0..w {
+        // Serpentine scanning: alternate rows are processed right-to-left, which
+        // visibly reduces directional worming artifacts.
+        let reverse = serpentine && y % 2 == 1;
+        for step in 0..w {
+            let x = if reverse { w - 1 - step } else { step };
             let idx = y * w + x;
             let old_pixel = result[idx];
             let new_pixel = if old_pixel > threshold { 255 } else { 0 };
             result[idx] = new_pixel;
-            
+
             let error = old_pixel as i16 - new_pixel as i16;
-            
-            // Distribute error to neighboring pixels
-            if x + 1 < w {
-                let idx_right = y * w + (x + 1);
-                result[idx_right] = clamp_pixel(result[idx_right] as i16 + error * 7 / 16);
-            }
-            
-            if y + 1 < h {
-                if x > 0 {
-                    let idx_below_left = (y + 1) * w + (x - 1);
-                    result[idx_below_left] = clamp_pixel(result[idx_below_left] as i16 + error * 3 / 16);
-                }
-                
-                let idx_below = (y + 1) * w + x;
-                result[idx_below] = clamp_pixel(result[idx_below] as i16 + error * 5 / 16);
-                
-                if x + 1 < w {
-                    let idx_below_right = (y + 1) * w + (x + 1);
-                    result[idx_below_right] = clamp_pixel(result[idx_below_right] as i16 + error * 1 / 16);
+
+            for &(dx, dy, weight) in kernel.offsets {
+                // Mirror the x-offset on right-to-left rows.
+                let dx = if reverse { -dx } else { dx };
+                let nx = x as i32 + dx;
+                let ny = y as i32 + dy;
+                if nx < 0 || nx >= w as i32 || ny < 0 || ny >= h as i32 {
+                    continue;
                 }
+                let n_idx = ny as usize * w + nx as usize;
+                result[n_idx] = clamp_pixel(
+                    result[n_idx] as i16 + error * weight as i16 / kernel.divisor as i16,
+                );
             }
         }
     }
-    
+
     ProcessResult {
         success: true,
         error: String::new(),
@@ -131,28 +149,208 @@ fn bayer_dither_impl(pixels: &[u8], width: u32, height: u32, params: &[f32]) ->
         }
     };
     
-    let mut result = pixels.to_vec();
     let w = width as usize;
     let h = height as usize;
-    
+
+    let result = process_rows(w, h, 0u8, |y, row| {
+        for (x, out) in row.iter_mut().enumerate() {
+            let pixel = pixels[y * w + x];
+            let dither_value = bayer_matrix[(y % matrix_size) * matrix_size + (x % matrix_size)];
+            let adjusted_threshold = threshold as i16 + (dither_value as i16 - 128) / 4;
+
+            *out = if (pixel as i16) > adjusted_threshold { 255 } else { 0 };
+        }
+    });
+
+    ProcessResult {
+        success: true,
+        error: String::new(),
+        data: result,
+    }
+}
+
+fn quantize_impl(pixels: &[u8], width: u32, height: u32, params: &[f32]) -> ProcessResult {
+    if params.is_empty() {
+        return ProcessResult {
+            success: false,
+            error: "Quantize requires color count parameter".to_string(),
+            data: vec![],
+        };
+    }
+
+    let w = width as usize;
+    let h = height as usize;
+    let pixel_count = w * h;
+
+    if pixels.len() < pixel_count * 4 {
+        return ProcessResult {
+            success: false,
+            error: "Quantize expects 4 bytes (RGBA) per pixel".to_string(),
+            data: vec![],
+        };
+    }
+
+    // Clamp to 255 (not 256): the packed output prefixes the palette length as
+    // a single byte, so 256 would wrap to 0 and corrupt the format.
+    let num_colors = (params[0] as usize).clamp(1, 255);
+    let dither = params.len() > 1 && params[1] != 0.0;
+    let kmeans_iters = if params.len() > 2 { params[2] as usize } else { 2 };
+
+    // Gather RGB triples (alpha is ignored for palette selection).
+    let mut rgb: Vec<[f32; 3]> = Vec::with_capacity(pixel_count);
+    for i in 0..pixel_count {
+        let o = i * 4;
+        rgb.push([pixels[o] as f32, pixels[o + 1] as f32, pixels[o + 2] as f32]);
+    }
+
+    // --- Median-cut: seed the palette by recursively splitting boxes. ---
+    let mut boxes: Vec<Vec<usize>> = vec![(0..pixel_count).collect()];
+    while boxes.len() < num_colors {
+        // Find the box with the largest single-channel range.
+        let mut best_box = 0;
+        let mut best_channel = 0;
+        let mut best_range = -1.0f32;
+        for (bi, b) in boxes.iter().enumerate() {
+            if b.len() < 2 {
+                continue;
+            }
+            #[allow(clippy::needless_range_loop)]
+            for ch in 0..3 {
+                let (mut lo, mut hi) = (f32::MAX, f32::MIN);
+                for &p in b {
+                    lo = lo.min(rgb[p][ch]);
+                    hi = hi.max(rgb[p][ch]);
+                }
+                let range = hi - lo;
+                if range > best_range {
+                    best_range = range;
+                    best_box = bi;
+                    best_channel = ch;
+                }
+            }
+        }
+
+        if best_range <= 0.0 {
+            break; // No box has any color spread left to split.
+        }
+
+        let mut b = boxes.swap_remove(best_box);
+        b.sort_by(|&a, &c| {
+            rgb[a][best_channel]
+                .partial_cmp(&rgb[c][best_channel])
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        let mid = b.len() / 2;
+        let upper = b.split_off(mid);
+        boxes.push(b);
+        boxes.push(upper);
+    }
+
+    // Palette color of each box is its average.
+    let mut palette: Vec<[f32; 3]> = boxes
+        .iter()
+        .map(|b| {
+            let mut acc = [0.0f32; 3];
+            for &p in b {
+                for ch in 0..3 {
+                    acc[ch] += rgb[p][ch];
+                }
+            }
+            let n = b.len().max(1) as f32;
+            [acc[0] / n, acc[1] / n, acc[2] / n]
+        })
+        .collect();
+
+    // --- K-means refinement. ---
+    for _ in 0..kmeans_iters {
+        let mut sums = vec![[0.0f32; 3]; palette.len()];
+        let mut counts = vec![0u32; palette.len()];
+        for c in &rgb {
+            let best = nearest_palette_index(&palette, *c);
+            for ch in 0..3 {
+                sums[best][ch] += c[ch];
+            }
+            counts[best] += 1;
+        }
+        for (i, &count) in counts.iter().enumerate() {
+            if count > 0 {
+                for ch in 0..3 {
+                    palette[i][ch] = sums[i][ch] / count as f32;
+                }
+            }
+        }
+    }
+
+    // --- Remap, optionally with Floyd-Steinberg error diffusion in RGB space. ---
+    let mut work = rgb.clone();
+    let mut indices = vec![0u8; pixel_count];
     for y in 0..h {
         for x in 0..w {
             let idx = y * w + x;
-            let pixel = result[idx];
-            let dither_value = bayer_matrix[(y % matrix_size) * matrix_size + (x % matrix_size)];
-            let adjusted_threshold = threshold as i16 + (dither_value as i16 - 128) / 4;
-            
-            result[idx] = if (pixel as i16) > adjusted_threshold { 255 } else { 0 };
+            let best = nearest_palette_index(&palette, work[idx]);
+            indices[idx] = best as u8;
+
+            if dither {
+                let error = [
+                    work[idx][0] - palette[best][0],
+                    work[idx][1] - palette[best][1],
+                    work[idx][2] - palette[best][2],
+                ];
+                let spread = |buf: &mut Vec<[f32; 3]>, i: usize, f: f32| {
+                    for ch in 0..3 {
+                        buf[i][ch] += error[ch] * f;
+                    }
+                };
+                if x + 1 < w {
+                    spread(&mut work, idx + 1, 7.0 / 16.0);
+                }
+                if y + 1 < h {
+                    if x > 0 {
+                        spread(&mut work, (y + 1) * w + (x - 1), 3.0 / 16.0);
+                    }
+                    spread(&mut work, (y + 1) * w + x, 5.0 / 16.0);
+                    if x + 1 < w {
+                        spread(&mut work, (y + 1) * w + (x + 1), 1.0 / 16.0);
+                    }
+                }
+            }
         }
     }
-    
+
+    // Pack the result: palette length prefix + RGBA entries (4 bytes each) + indices.
+    let mut data = Vec::with_capacity(1 + palette.len() * 4 + pixel_count);
+    data.push(palette.len() as u8);
+    for c in &palette {
+        data.push(c[0].round().clamp(0.0, 255.0) as u8);
+        data.push(c[1].round().clamp(0.0, 255.0) as u8);
+        data.push(c[2].round().clamp(0.0, 255.0) as u8);
+        data.push(255);
+    }
+    data.extend_from_slice(&indices);
+
     ProcessResult {
         success: true,
         error: String::new(),
-        data: result,
+        data,
     }
 }
 
+fn nearest_palette_index(palette: &[[f32; 3]], color: [f32; 3]) -> usize {
+    let mut best = 0;
+    let mut best_dist = f32::MAX;
+    for (i, p) in palette.iter().enumerate() {
+        let dr = color[0] - p[0];
+        let dg = color[1] - p[1];
+        let db = color[2] - p[2];
+        let dist = dr * dr + dg * dg + db * db;
+        if dist < best_dist {
+            best_dist = dist;
+            best = i;
+        }
+    }
+    best
+}
+
 fn flood_fill_impl(pixels: &[u8], width: u32, height: u32, params: &[f32]) -> ProcessResult {
     if params.len() < 3 {
         return ProcessResult {
@@ -229,29 +427,223 @@ fn box_blur_impl(pixels: &[u8], width: u32, height: u32, params: &[f32]) -> Proc
     let radius = params[0] as usize;
     let w = width as usize;
     let h = height as usize;
-    let mut result = vec![0u8; w * h];
-    
-    for y in 0..h {
-        for x in 0..w {
+
+    let result = process_rows(w, h, 0u8, |y, row| {
+        for (x, out) in row.iter_mut().enumerate() {
             let mut sum = 0u32;
             let mut count = 0u32;
-            
+
             let y_start = y.saturating_sub(radius);
             let y_end = (y + radius + 1).min(h);
             let x_start = x.saturating_sub(radius);
             let x_end = (x + radius + 1).min(w);
-            
+
             for blur_y in y_start..y_end {
                 for blur_x in x_start..x_end {
                     sum += pixels[blur_y * w + blur_x] as u32;
                     count += 1;
                 }
             }
-            
-            result[y * w + x] = (sum / count) as u8;
+
+            *out = (sum / count) as u8;
         }
+    });
+
+    ProcessResult {
+        success: true,
+        error: String::new(),
+        data: result,
     }
-    
+}
+
+fn gaussian_blur_impl(pixels: &[u8], width: u32, height: u32, params: &[f32]) -> ProcessResult {
+    if params.is_empty() {
+        return ProcessResult {
+            success: false,
+            error: "Gaussian blur requires sigma parameter".to_string(),
+            data: vec![],
+        };
+    }
+
+    let sigma = params[0];
+    if sigma <= 0.0 {
+        return ProcessResult {
+            success: false,
+            error: "Gaussian blur requires positive sigma".to_string(),
+            data: vec![],
+        };
+    }
+
+    let w = width as usize;
+    let h = height as usize;
+    let blurred = gaussian_blur_f32(pixels, w, h, sigma);
+
+    let result = blurred
+        .iter()
+        .map(|&v| v.round().clamp(0.0, 255.0) as u8)
+        .collect();
+
+    ProcessResult {
+        success: true,
+        error: String::new(),
+        data: result,
+    }
+}
+
+fn unsharp_mask_impl(pixels: &[u8], width: u32, height: u32, params: &[f32]) -> ProcessResult {
+    if params.len() < 2 {
+        return ProcessResult {
+            success: false,
+            error: "Unsharp mask requires sigma and amount parameters".to_string(),
+            data: vec![],
+        };
+    }
+
+    let sigma = params[0];
+    let amount = params[1];
+    let threshold = if params.len() > 2 { params[2] } else { 0.0 };
+
+    if sigma <= 0.0 {
+        return ProcessResult {
+            success: false,
+            error: "Unsharp mask requires positive sigma".to_string(),
+            data: vec![],
+        };
+    }
+
+    let w = width as usize;
+    let h = height as usize;
+    let blurred = gaussian_blur_f32(pixels, w, h, sigma);
+
+    let result = pixels
+        .iter()
+        .zip(blurred.iter())
+        .map(|(&p, &b)| {
+            let orig = p as f32;
+            let diff = orig - b;
+            // Only sharpen differences above the threshold to avoid amplifying
+            // noise in otherwise flat areas.
+            if diff.abs() < threshold {
+                p
+            } else {
+                (orig + amount * diff).round().clamp(0.0, 255.0) as u8
+            }
+        })
+        .collect();
+
+    ProcessResult {
+        success: true,
+        error: String::new(),
+        data: result,
+    }
+}
+
+/// Build a normalized 1-D Gaussian kernel for the given sigma.
+fn gaussian_kernel(sigma: f32) -> Vec<f32> {
+    let radius = (3.0 * sigma).ceil() as usize;
+    let mut kernel = Vec::with_capacity(2 * radius + 1);
+    let two_sigma_sq = 2.0 * sigma * sigma;
+    for i in -(radius as i32)..=(radius as i32) {
+        kernel.push((-(i * i) as f32 / two_sigma_sq).exp());
+    }
+    let sum: f32 = kernel.iter().sum();
+    for k in &mut kernel {
+        *k /= sum;
+    }
+    kernel
+}
+
+/// Separable Gaussian blur over an f32 buffer with border-replicate edges.
+fn gaussian_blur_f32(pixels: &[u8], w: usize, h: usize, sigma: f32) -> Vec<f32> {
+    let kernel = gaussian_kernel(sigma);
+    let radius = kernel.len() / 2;
+
+    // Horizontal pass.
+    let temp = process_rows(w, h, 0.0f32, |y, row| {
+        for (x, out) in row.iter_mut().enumerate() {
+            let mut acc = 0.0f32;
+            for (k, &weight) in kernel.iter().enumerate() {
+                let sx = (x as i32 + k as i32 - radius as i32).clamp(0, w as i32 - 1) as usize;
+                acc += pixels[y * w + sx] as f32 * weight;
+            }
+            *out = acc;
+        }
+    });
+
+    // Vertical pass.
+    process_rows(w, h, 0.0f32, |y, row| {
+        for (x, out) in row.iter_mut().enumerate() {
+            let mut acc = 0.0f32;
+            for (k, &weight) in kernel.iter().enumerate() {
+                let sy = (y as i32 + k as i32 - radius as i32).clamp(0, h as i32 - 1) as usize;
+                acc += temp[sy * w + x] * weight;
+            }
+            *out = acc;
+        }
+    })
+}
+
+fn perspective_warp_impl(pixels: &[u8], width: u32, height: u32, params: &[f32]) -> ProcessResult {
+    if params.len() < 8 {
+        return ProcessResult {
+            success: false,
+            error: "Perspective warp requires four source corner points (8 params)".to_string(),
+            data: vec![],
+        };
+    }
+
+    let w = width as usize;
+    let h = height as usize;
+    if w < 2 || h < 2 {
+        return ProcessResult {
+            success: false,
+            error: "Perspective warp requires an image at least 2x2".to_string(),
+            data: vec![],
+        };
+    }
+
+    // Source quad corners, in the same order as the output rectangle corners
+    // below: top-left, top-right, bottom-right, bottom-left.
+    let src = [
+        (params[0], params[1]),
+        (params[2], params[3]),
+        (params[4], params[5]),
+        (params[6], params[7]),
+    ];
+    let dst = [
+        (0.0, 0.0),
+        ((w - 1) as f32, 0.0),
+        ((w - 1) as f32, (h - 1) as f32),
+        (0.0, (h - 1) as f32),
+    ];
+
+    // Solve for the homography mapping destination -> source.
+    let homography = match solve_homography(&dst, &src) {
+        Some(hm) => hm,
+        None => return ProcessResult {
+            success: false,
+            error: "Degenerate corner configuration; homography is not solvable".to_string(),
+            data: vec![],
+        },
+    };
+
+    let mut result = vec![0u8; w * h];
+    for y in 0..h {
+        for x in 0..w {
+            let fx = x as f32;
+            let fy = y as f32;
+            let denom = homography[6] * fx + homography[7] * fy + 1.0;
+            if denom.abs() < 1e-9 {
+                continue;
+            }
+            let sx = (homography[0] * fx + homography[1] * fy + homography[2]) / denom;
+            let sy = (homography[3] * fx + homography[4] * fy + homography[5]) / denom;
+            if let Some(sample) = bilinear_sample(pixels, w, h, sx, sy) {
+                result[y * w + x] = sample;
+            }
+        }
+    }
+
     ProcessResult {
         success: true,
         error: String::new(),
@@ -259,6 +651,222 @@ fn box_blur_impl(pixels: &[u8], width: u32, height: u32, params: &[f32]) -> Proc
     }
 }
 
+/// Solve the eight-unknown homography mapping `from[i]` to `to[i]` for four
+/// point correspondences. Returns `[a,b,c,d,e,f,g,h]` where the last entry of
+/// the 3x3 matrix is fixed at 1.
+fn solve_homography(from: &[(f32, f32); 4], to: &[(f32, f32); 4]) -> Option<[f32; 8]> {
+    let mut a = [[0.0f32; 8]; 8];
+    let mut b = [0.0f32; 8];
+    for i in 0..4 {
+        let (x, y) = from[i];
+        let (u, v) = to[i];
+        let r0 = i * 2;
+        a[r0] = [x, y, 1.0, 0.0, 0.0, 0.0, -x * u, -y * u];
+        b[r0] = u;
+        let r1 = r0 + 1;
+        a[r1] = [0.0, 0.0, 0.0, x, y, 1.0, -x * v, -y * v];
+        b[r1] = v;
+    }
+
+    // Gaussian elimination with partial pivoting.
+    for col in 0..8 {
+        let mut pivot = col;
+        for row in (col + 1)..8 {
+            if a[row][col].abs() > a[pivot][col].abs() {
+                pivot = row;
+            }
+        }
+        if a[pivot][col].abs() < 1e-9 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+
+        for row in 0..8 {
+            if row == col {
+                continue;
+            }
+            let factor = a[row][col] / a[col][col];
+            #[allow(clippy::needless_range_loop)]
+            for k in col..8 {
+                a[row][k] -= factor * a[col][k];
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+
+    let mut h = [0.0f32; 8];
+    for i in 0..8 {
+        h[i] = b[i] / a[i][i];
+    }
+    Some(h)
+}
+
+/// Bilinear sample of a grayscale buffer; returns `None` when out of bounds.
+fn bilinear_sample(pixels: &[u8], w: usize, h: usize, sx: f32, sy: f32) -> Option<u8> {
+    if sx < 0.0 || sy < 0.0 || sx > (w - 1) as f32 || sy > (h - 1) as f32 {
+        return None;
+    }
+    let x0 = sx.floor() as usize;
+    let y0 = sy.floor() as usize;
+    let x1 = (x0 + 1).min(w - 1);
+    let y1 = (y0 + 1).min(h - 1);
+    let tx = sx - x0 as f32;
+    let ty = sy - y0 as f32;
+
+    let p00 = pixels[y0 * w + x0] as f32;
+    let p10 = pixels[y0 * w + x1] as f32;
+    let p01 = pixels[y1 * w + x0] as f32;
+    let p11 = pixels[y1 * w + x1] as f32;
+
+    let top = p00 + (p10 - p00) * tx;
+    let bottom = p01 + (p11 - p01) * tx;
+    Some((top + (bottom - top) * ty).round().clamp(0.0, 255.0) as u8)
+}
+
+fn perceptual_diff_impl(pixels: &[u8], width: u32, height: u32, params: &[f32]) -> ProcessResult {
+    let w = width as usize;
+    let h = height as usize;
+    let pixel_count = w * h;
+
+    // The two images are concatenated in `pixels`; an explicit offset for the
+    // second image can be supplied as params[0].
+    let offset = if !params.is_empty() && params[0] >= 0.0 {
+        params[0] as usize
+    } else {
+        pixel_count
+    };
+
+    if pixels.len() < offset + pixel_count {
+        return ProcessResult {
+            success: false,
+            error: "Perceptual diff requires two full image buffers".to_string(),
+            data: vec![],
+        };
+    }
+
+    let a = &pixels[..pixel_count];
+    let b = &pixels[offset..offset + pixel_count];
+
+    let window = if params.len() > 1 { (params[1] as usize).max(1) } else { 8 };
+    let win_w = window.min(w);
+    let win_h = window.min(h);
+    let win_area = (win_w * win_h) as f32;
+
+    // Stabilizers for the SSIM denominator (Wang et al., 8-bit dynamic range).
+    const C1: f32 = 6.5025; // (0.01 * 255)^2
+    const C2: f32 = 58.5225; // (0.03 * 255)^2
+
+    let mut diffmap = vec![0u8; pixel_count];
+    let mut ssim_sum = 0.0f32;
+
+    for y in 0..h {
+        // Anchor the window so it stays inside the image.
+        let y0 = y.min(h - win_h);
+        for x in 0..w {
+            let x0 = x.min(w - win_w);
+
+            let (mut sum_a, mut sum_b) = (0.0f32, 0.0f32);
+            let (mut sum_aa, mut sum_bb, mut sum_ab) = (0.0f32, 0.0f32, 0.0f32);
+            for wy in 0..win_h {
+                for wx in 0..win_w {
+                    let idx = (y0 + wy) * w + (x0 + wx);
+                    let va = a[idx] as f32;
+                    let vb = b[idx] as f32;
+                    sum_a += va;
+                    sum_b += vb;
+                    sum_aa += va * va;
+                    sum_bb += vb * vb;
+                    sum_ab += va * vb;
+                }
+            }
+
+            let mu1 = sum_a / win_area;
+            let mu2 = sum_b / win_area;
+            let var1 = sum_aa / win_area - mu1 * mu1;
+            let var2 = sum_bb / win_area - mu2 * mu2;
+            let cov = sum_ab / win_area - mu1 * mu2;
+
+            let ssim = ((2.0 * mu1 * mu2 + C1) * (2.0 * cov + C2))
+                / ((mu1 * mu1 + mu2 * mu2 + C1) * (var1 + var2 + C2));
+
+            ssim_sum += ssim;
+            diffmap[y * w + x] = (255.0 * (1.0 - ssim)).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+
+    let mean_ssim = if pixel_count > 0 { ssim_sum / pixel_count as f32 } else { 1.0 };
+
+    // Pack the diffmap followed by the mean SSIM score (little-endian f32).
+    let mut data = Vec::with_capacity(pixel_count + 4);
+    data.extend_from_slice(&diffmap);
+    data.extend_from_slice(&mean_ssim.to_le_bytes());
+
+    ProcessResult {
+        success: true,
+        error: String::new(),
+        data,
+    }
+}
+
+/// Fill a `w * h` buffer one output row at a time. With the `rayon` feature the
+/// rows are distributed across the global thread pool; otherwise they run
+/// sequentially. The per-row closure must be self-contained (reads only its
+/// inputs, writes only its row) so the two paths are equivalent.
+fn process_rows<T, F>(w: usize, h: usize, init: T, f: F) -> Vec<T>
+where
+    T: Clone + Send,
+    F: Fn(usize, &mut [T]) + Sync + Send,
+{
+    let mut result = vec![init; w * h];
+
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        result
+            .par_chunks_mut(w.max(1))
+            .enumerate()
+            .for_each(|(y, row)| f(y, row));
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    {
+        result
+            .chunks_mut(w.max(1))
+            .enumerate()
+            .for_each(|(y, row)| f(y, row));
+    }
+
+    result
+}
+
+/// Report whether the crate was built with the parallel (`rayon`) execution
+/// path enabled, so the JS host can decide whether to offer threaded options.
+#[wasm_bindgen]
+pub fn is_parallel() -> bool {
+    cfg!(feature = "rayon")
+}
+
+/// Configure the size of the parallel thread pool. Only meaningful with the
+/// `rayon` feature (and wasm threads opt-in); returns `true` when the pool was
+/// installed, `false` otherwise. Has no effect if a pool already exists.
+#[wasm_bindgen]
+pub fn set_thread_count(count: usize) -> bool {
+    #[cfg(feature = "rayon")]
+    {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(count)
+            .build_global()
+            .is_ok()
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    {
+        let _ = count;
+        false
+    }
+}
+
 fn clamp_pixel(value: i16) -> u8 {
     if value < 0 {
         0
@@ -269,6 +877,51 @@ fn clamp_pixel(value: i16) -> u8 {
     }
 }
 
+// Error-diffusion kernels. Each entry is (dx, dy, weight) relative to the
+// current pixel; the accumulated error is scaled by weight/divisor.
+struct DitherKernel {
+    offsets: &'static [(i32, i32, i32)],
+    divisor: i32,
+}
+
+const FLOYD_STEINBERG_KERNEL: DitherKernel = DitherKernel {
+    offsets: &[(1, 0, 7), (-1, 1, 3), (0, 1, 5), (1, 1, 1)],
+    divisor: 16,
+};
+
+// Atkinson deliberately diffuses only 6/8 of the error to boost contrast.
+const ATKINSON_KERNEL: DitherKernel = DitherKernel {
+    offsets: &[(1, 0, 1), (2, 0, 1), (-1, 1, 1), (0, 1, 1), (1, 1, 1), (0, 2, 1)],
+    divisor: 8,
+};
+
+const JARVIS_JUDICE_NINKE_KERNEL: DitherKernel = DitherKernel {
+    offsets: &[
+        (1, 0, 7), (2, 0, 5),
+        (-2, 1, 3), (-1, 1, 5), (0, 1, 7), (1, 1, 5), (2, 1, 3),
+        (-2, 2, 1), (-1, 2, 3), (0, 2, 5), (1, 2, 3), (2, 2, 1),
+    ],
+    divisor: 48,
+};
+
+const STUCKI_KERNEL: DitherKernel = DitherKernel {
+    offsets: &[
+        (1, 0, 8), (2, 0, 4),
+        (-2, 1, 2), (-1, 1, 4), (0, 1, 8), (1, 1, 4), (2, 1, 2),
+        (-2, 2, 1), (-1, 2, 2), (0, 2, 4), (1, 2, 2), (2, 2, 1),
+    ],
+    divisor: 42,
+};
+
+const SIERRA_KERNEL: DitherKernel = DitherKernel {
+    offsets: &[
+        (1, 0, 5), (2, 0, 3),
+        (-2, 1, 2), (-1, 1, 4), (0, 1, 5), (1, 1, 4), (2, 1, 2),
+        (-1, 2, 2), (0, 2, 3), (1, 2, 2),
+    ],
+    divisor: 32,
+};
+
 // Bayer matrices for ordered dithering
 const BAYER_2X2: &[u8] = &[
     0, 128,
@@ -291,4 +944,128 @@ const BAYER_8X8: &[u8] = &[
     204, 76, 236, 108, 196, 68, 228, 100,
     60, 188, 28, 156, 52, 180, 20, 148,
     252, 124, 220, 92, 244, 116, 212, 84
-];
\ No newline at end of file
+];
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dither_kernels_have_the_expected_weight_totals() {
+        let sum = |k: &DitherKernel| k.offsets.iter().map(|&(_, _, w)| w).sum::<i32>();
+        // Error-preserving kernels diffuse the whole error.
+        assert_eq!(sum(&FLOYD_STEINBERG_KERNEL), FLOYD_STEINBERG_KERNEL.divisor);
+        assert_eq!(sum(&JARVIS_JUDICE_NINKE_KERNEL), JARVIS_JUDICE_NINKE_KERNEL.divisor);
+        assert_eq!(sum(&STUCKI_KERNEL), STUCKI_KERNEL.divisor);
+        assert_eq!(sum(&SIERRA_KERNEL), SIERRA_KERNEL.divisor);
+        // Atkinson deliberately drops 2/8 of the error to boost contrast.
+        assert_eq!(sum(&ATKINSON_KERNEL), 6);
+        assert_eq!(ATKINSON_KERNEL.divisor, 8);
+    }
+
+    #[test]
+    fn dither_kernels_produce_binary_output() {
+        // A gray ramp run through every kernel (and serpentine) must yield a
+        // strictly 1-bit result.
+        let pixels: Vec<u8> = (0..16u16).map(|v| (v * 16) as u8).collect();
+        for kernel_id in 0..5 {
+            for &serp in &[0.0f32, 1.0] {
+                let result = floyd_steinberg_impl(&pixels, 4, 4, &[128.0, kernel_id as f32, serp]);
+                assert!(result.success);
+                assert!(result.data.iter().all(|&p| p == 0 || p == 255));
+            }
+        }
+    }
+
+    #[test]
+    fn quantize_respects_color_count_and_index_bounds() {
+        // A gradient RGBA input asking for 4 colors.
+        let mut pixels = Vec::new();
+        for i in 0..64u32 {
+            pixels.extend_from_slice(&[(i * 4) as u8, (255 - i * 3) as u8, (i * 7) as u8, 255]);
+        }
+        let result = quantize_impl(&pixels, 8, 8, &[4.0]);
+        assert!(result.success);
+
+        let palette_len = result.data[0] as usize;
+        assert!((1..=4).contains(&palette_len));
+        let indices = &result.data[1 + palette_len * 4..];
+        assert_eq!(indices.len(), 64);
+        assert!(indices.iter().all(|&i| (i as usize) < palette_len));
+    }
+
+    #[test]
+    fn quantize_of_a_flat_field_yields_one_color() {
+        let pixels = vec![30u8; 16 * 4]; // 16 identical RGBA pixels
+        let result = quantize_impl(&pixels, 4, 4, &[8.0]);
+        assert!(result.success);
+        assert_eq!(result.data[0], 1, "a single-color input needs one palette entry");
+        assert_eq!(&result.data[1..5], &[30, 30, 30, 255]);
+        assert!(result.data[5..].iter().all(|&i| i == 0));
+    }
+
+    #[test]
+    fn gaussian_kernel_is_normalized_and_symmetric() {
+        let kernel = gaussian_kernel(1.5);
+        let sum: f32 = kernel.iter().sum();
+        assert!((sum - 1.0).abs() < 1e-5, "kernel should sum to 1, got {sum}");
+        // Symmetric about the center tap.
+        for i in 0..kernel.len() / 2 {
+            let mirror = kernel.len() - 1 - i;
+            assert!((kernel[i] - kernel[mirror]).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn gaussian_blur_preserves_a_flat_field() {
+        // A uniform image must stay uniform after blurring (border replicate).
+        let pixels = vec![120u8; 6 * 5];
+        let out = gaussian_blur_f32(&pixels, 6, 5, 1.0);
+        for v in out {
+            assert!((v - 120.0).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn unsharp_mask_leaves_a_flat_field_unchanged() {
+        // With no local contrast there is nothing to sharpen.
+        let pixels = vec![90u8; 4 * 4];
+        let result = unsharp_mask_impl(&pixels, 4, 4, &[1.0, 2.0]);
+        assert!(result.success);
+        assert_eq!(result.data, pixels);
+    }
+
+    #[test]
+    fn homography_of_the_output_rectangle_is_the_identity() {
+        // Source corners equal to the destination rectangle must yield an
+        // identity map (the last row collapses, the top-left block is I).
+        let dst = [(0.0, 0.0), (3.0, 0.0), (3.0, 2.0), (0.0, 2.0)];
+        let h = solve_homography(&dst, &dst).expect("solvable");
+        let expected = [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0];
+        for (got, want) in h.iter().zip(expected.iter()) {
+            assert!((got - want).abs() < 1e-4, "got {h:?}");
+        }
+    }
+
+    #[test]
+    fn bilinear_sample_interpolates_and_rejects_out_of_bounds() {
+        let pixels = [0u8, 100, 0, 200];
+        // Midpoint of the top edge averages the two top samples.
+        assert_eq!(bilinear_sample(&pixels, 2, 2, 0.5, 0.0), Some(50));
+        assert_eq!(bilinear_sample(&pixels, 2, 2, 0.0, 0.0), Some(0));
+        assert_eq!(bilinear_sample(&pixels, 2, 2, -0.5, 0.0), None);
+    }
+
+    #[test]
+    fn perceptual_diff_of_identical_images_scores_one() {
+        // Two copies of the same image: SSIM ≈ 1 and an all-zero diff map.
+        let img: Vec<u8> = (0..64u16).map(|v| (v * 3) as u8).collect();
+        let mut both = img.clone();
+        both.extend_from_slice(&img);
+        let result = perceptual_diff_impl(&both, 8, 8, &[]);
+        assert!(result.success);
+
+        let score = f32::from_le_bytes(result.data[64..68].try_into().unwrap());
+        assert!((score - 1.0).abs() < 1e-3, "score was {score}");
+        assert!(result.data[..64].iter().all(|&b| b == 0));
+    }
+}